@@ -1,6 +1,6 @@
 //! `sprint-boot-layertools` extracts a layered Spring Boot Jar.
 
-use std::io::{Cursor, Read};
+use std::io::{Cursor, Read, Seek};
 use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Context};
@@ -9,8 +9,12 @@ use yaml_rust::{Yaml, YamlLoader};
 use zip::ZipArchive;
 
 use crate::manifest::JarManifest;
+use crate::timestamp::{resolve_mtime, FALLBACK_TIMESTAMP};
 
 mod manifest;
+mod run;
+mod timestamp;
+mod tools;
 
 fn main() -> anyhow::Result<()> {
     let cmd = command!()
@@ -18,7 +22,7 @@ fn main() -> anyhow::Result<()> {
             Arg::with_name("jar")
                 .required(true)
                 .takes_value(true)
-                .help("The layered Spring Boot jar to extract")
+                .help("The layered Spring Boot jar to extract, or '-' to read it from stdin")
                 .value_parser(value_parser!(PathBuf)),
         )
         .subcommand(Command::new("list").about("List layers from the jar that can be extracted"))
@@ -41,9 +45,51 @@ fn main() -> anyhow::Result<()> {
                         .takes_value(true)
                         .multiple_occurrences(true)
                         .use_delimiter(true),
+                )
+                .arg(
+                    Arg::new("launcher")
+                        .help("Extracts the jar as a runnable exploded application instead of grouping files by layer")
+                        .long("launcher")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::new("tools")
+                        .help("Extracts a slim application.jar plus a sibling lib/ directory of dependencies, instead of grouping files by layer")
+                        .long("tools")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::new("use-layer-tools-timestamp")
+                        .help("Stamps every extracted file with a single fixed timestamp instead of each entry's own, for reproducible extraction")
+                        .long("use-layer-tools-timestamp")
+                        .takes_value(false),
+                )
+                .arg(
+                    Arg::new("timestamp")
+                        .help("The fixed Unix timestamp (seconds) to use with --use-layer-tools-timestamp, or as a fallback for entries without a valid timestamp")
+                        .long("timestamp")
+                        .takes_value(true)
+                        .value_parser(value_parser!(i64)),
+                )
+                .arg(
+                    Arg::new("force")
+                        .help("Overwrite the destination if it already contains files, instead of aborting")
+                        .long("force")
+                        .takes_value(false),
                 ),
         )
         .subcommand(Command::new("classpath").about("List classpath dependencies from the jar"))
+        .subcommand(
+            Command::new("run")
+                .about("Resolves the classpath and launches the application with java")
+                .trailing_var_arg(true)
+                .arg(
+                    Arg::new("args")
+                        .help("Arguments to forward to the application")
+                        .multiple_values(true)
+                        .allow_hyphen_values(true),
+                ),
+        )
         .subcommand_required(true)
         .get_matches();
 
@@ -51,33 +97,147 @@ fn main() -> anyhow::Result<()> {
         .get_one::<PathBuf>("jar")
         .with_context(|| "Missing jar")?;
 
-    let map = mmarinus::Map::load(jar, mmarinus::Private, mmarinus::perms::Read)
-        .with_context(|| "Failed to open jar with mmap")?;
+    if jar.as_os_str() == "-" {
+        let mut buffer = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut buffer)
+            .with_context(|| "Failed to read jar from stdin")?;
+        let cache_key = stdin_cache_key(&buffer);
+
+        let mut zip = ZipArchive::new(Cursor::new(buffer.as_slice()))
+            .with_context(|| "Failed to open jar archive")?;
+        let manifest = JarManifest::from_zip(&mut zip)?;
 
-    let mut zip =
-        ZipArchive::new(Cursor::new(map.as_ref())).with_context(|| "Failed to open jar archive")?;
+        dispatch(&cmd, zip, manifest, &cache_key)
+    } else {
+        let map = mmarinus::Map::load(jar, mmarinus::Private, mmarinus::perms::Read)
+            .with_context(|| "Failed to open jar with mmap")?;
 
-    let manifest = JarManifest::from_zip(&mut zip)?;
+        let mut zip = ZipArchive::new(Cursor::new(map.as_ref()))
+            .with_context(|| "Failed to open jar archive")?;
+        let manifest = JarManifest::from_zip(&mut zip)?;
 
+        let cache_key = file_cache_key(jar)?;
+
+        dispatch(&cmd, zip, manifest, &cache_key)
+    }
+}
+
+/// Derives a stable cache key for a jar streamed from stdin, since it has no filesystem path of
+/// its own: a hash of its contents, so repeated `run`s of the same stream reuse the cache.
+fn stdin_cache_key(buffer: &[u8]) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    buffer.hash(&mut hasher);
+    format!("stdin-{:x}", hasher.finish())
+}
+
+/// Derives a cache key for a jar on disk from its canonical path, size and modification time, so
+/// that `run`'s cache is invalidated when the jar is rebuilt at the same path rather than reusing
+/// stale extracted classes and dependency jars.
+fn file_cache_key(jar: &Path) -> anyhow::Result<String> {
+    use std::hash::{Hash, Hasher};
+    let canonical =
+        std::fs::canonicalize(jar).with_context(|| format!("failed to resolve {:?}", jar))?;
+    let metadata = std::fs::metadata(&canonical)
+        .with_context(|| format!("failed to stat {:?}", canonical))?;
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    canonical.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    metadata.modified().ok().hash(&mut hasher);
+    Ok(format!("file-{:x}", hasher.finish()))
+}
+
+/// Dispatches to the requested subcommand, generic over the jar's backing reader so it works
+/// whether the jar was mmap'd from disk or buffered in from stdin.
+fn dispatch<R: Read + Seek>(
+    cmd: &clap::ArgMatches,
+    zip: ZipArchive<R>,
+    manifest: JarManifest,
+    cache_key: &str,
+) -> anyhow::Result<()> {
     match cmd.subcommand() {
         Some(("list", _)) => list(zip, manifest),
-        Some(("extract", args)) => extract(
+        Some(("extract", args)) => {
+            let destination = args
+                .get_one::<PathBuf>("destination")
+                .with_context(|| "invalid extract destination")?;
+            let reproducible = args.is_present("use-layer-tools-timestamp");
+            let fallback_timestamp = args
+                .get_one::<i64>("timestamp")
+                .copied()
+                .unwrap_or(FALLBACK_TIMESTAMP);
+            let force = args.is_present("force");
+
+            if args.is_present("launcher") {
+                extract_launcher(zip, destination, reproducible, fallback_timestamp, force)
+            } else if args.is_present("tools") {
+                tools::extract_tools(
+                    zip,
+                    manifest,
+                    destination,
+                    reproducible,
+                    fallback_timestamp,
+                    force,
+                )
+            } else {
+                extract(
+                    zip,
+                    manifest,
+                    destination,
+                    args.get_many::<String>("layers")
+                        .map(|iter| iter.map(String::as_str).collect())
+                        .unwrap_or_default(),
+                    reproducible,
+                    fallback_timestamp,
+                    force,
+                )
+            }
+        }
+        Some(("classpath", _)) => classpath(zip, manifest),
+        Some(("run", args)) => run::run(
             zip,
             manifest,
-            args.get_one::<PathBuf>("destination")
-                .with_context(|| "invalid extract destination")?,
-            args.get_many::<String>("layers")
+            cache_key,
+            args.get_many::<String>("args")
                 .map(|iter| iter.map(String::as_str).collect())
                 .unwrap_or_default(),
         ),
-        Some(("classpath", _)) => classpath(zip, manifest),
         _ => bail!("unexpected subcommand composition"),
     }
 }
 
+/// Ensures it's safe to extract into `destination`: fails fast if it already exists and
+/// contains something (a non-empty directory, or a file), unless `force` is set. Checked up
+/// front, before any file is written, so a conflict aborts cleanly rather than leaving a
+/// half-extracted tree.
+pub(crate) fn ensure_extraction_allowed(destination: &Path, force: bool) -> anyhow::Result<()> {
+    if force {
+        return Ok(());
+    }
+
+    let occupied = if destination.is_dir() {
+        destination
+            .read_dir()
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false)
+    } else {
+        destination.exists()
+    };
+
+    anyhow::ensure!(
+        !occupied,
+        "destination {:?} already contains files; pass --force to overwrite",
+        destination
+    );
+
+    Ok(())
+}
+
 /// Extracts the layer index from the Jar, in YAML form.
-fn layers_yaml(
-    zip: &mut ZipArchive<Cursor<&[u8]>>,
+fn layers_yaml<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
     manifest: &JarManifest,
 ) -> anyhow::Result<Yaml> {
     let index = {
@@ -99,8 +259,8 @@ fn layers_yaml(
 }
 
 /// Extracts the classpath index from the Jar, in YAML form.
-fn classpath_yaml(
-    zip: &mut ZipArchive<Cursor<&[u8]>>,
+fn classpath_yaml<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
     manifest: &JarManifest,
 ) -> anyhow::Result<Yaml> {
     let index = {
@@ -122,7 +282,7 @@ fn classpath_yaml(
 }
 
 /// Lists the names of the layers inside the Jar.
-fn list(mut zip: ZipArchive<Cursor<&[u8]>>, manifest: JarManifest) -> anyhow::Result<()> {
+fn list<R: Read + Seek>(mut zip: ZipArchive<R>, manifest: JarManifest) -> anyhow::Result<()> {
     layers_yaml(&mut zip, &manifest)?
         .as_vec()
         .with_context(|| "Invalid layer index yaml: expected array")?
@@ -134,7 +294,7 @@ fn list(mut zip: ZipArchive<Cursor<&[u8]>>, manifest: JarManifest) -> anyhow::Re
     Ok(())
 }
 
-fn classpath(mut zip: ZipArchive<Cursor<&[u8]>>, manifest: JarManifest) -> anyhow::Result<()> {
+fn classpath<R: Read + Seek>(mut zip: ZipArchive<R>, manifest: JarManifest) -> anyhow::Result<()> {
     classpath_yaml(&mut zip, &manifest)?
         .as_vec()
         .with_context(|| "Invalid classpath index yaml: expected array")?
@@ -145,16 +305,19 @@ fn classpath(mut zip: ZipArchive<Cursor<&[u8]>>, manifest: JarManifest) -> anyho
 }
 
 /// Extracts the layers inside the Jar in their own directory.
-fn extract(
-    mut zip: ZipArchive<Cursor<&[u8]>>,
+fn extract<R: Read + Seek>(
+    mut zip: ZipArchive<R>,
     manifest: JarManifest,
     destination: &PathBuf,
     layers: Vec<&str>,
+    reproducible: bool,
+    fallback_timestamp: i64,
+    force: bool,
 ) -> anyhow::Result<()> {
     std::fs::create_dir_all(destination)
         .with_context(|| "Failed to create destination directory")?;
 
-    layers_yaml(&mut zip, &manifest)?
+    let layer_entries: Vec<(String, Vec<String>)> = layers_yaml(&mut zip, &manifest)?
         .as_vec()
         .with_context(|| "Invalid layer index yaml: expected array")?
         .iter()
@@ -174,18 +337,106 @@ fn extract(
                                 .collect::<Vec<String>>()
                         })
                         .or_else(|| Some(Vec::default()))
-                        .map(|files| (name, files))
+                        .map(|files| (name.to_string(), files))
                 })
         })
-        .try_for_each(|(name, files)| extract_layer(&mut zip, destination, name, files))
+        .collect();
+
+    for (name, _) in &layer_entries {
+        let layer_destination = destination.join(name);
+        anyhow::ensure!(
+            layer_destination.starts_with(destination),
+            "invalid layer name: potential malicious use of relative path"
+        );
+        ensure_extraction_allowed(&layer_destination, force)?;
+    }
+
+    layer_entries.into_iter().try_for_each(|(name, files)| {
+        extract_layer(
+            &mut zip,
+            destination,
+            &name,
+            files,
+            reproducible,
+            fallback_timestamp,
+        )
+    })
+}
+
+/// Extracts the Jar as a runnable exploded application, preserving each entry's original path
+/// instead of grouping files into per-layer directories. This produces the layout expected by
+/// Spring Boot's loader (`org.springframework.boot.loader.JarLauncher`), so the result can be run
+/// directly with `java -cp <destination> org.springframework.boot.loader.JarLauncher`.
+fn extract_launcher<R: Read + Seek>(
+    mut zip: ZipArchive<R>,
+    destination: &PathBuf,
+    reproducible: bool,
+    fallback_timestamp: i64,
+    force: bool,
+) -> anyhow::Result<()> {
+    ensure_extraction_allowed(destination, force)?;
+
+    let mut output_paths = Vec::with_capacity(zip.len());
+    for i in 0..zip.len() {
+        let zip_file = zip
+            .by_index(i)
+            .with_context(|| format!("failed to read entry at index {}", i))?;
+
+        if zip_file.is_dir() {
+            continue;
+        }
+
+        let entry = zip_file
+            .enclosed_name()
+            .with_context(|| {
+                format!(
+                    "failed to determine enclosed name of entry: {}",
+                    zip_file.name()
+                )
+            })?
+            .to_owned();
+
+        let output_path = destination.join(&entry);
+        anyhow::ensure!(
+            output_path.starts_with(destination),
+            "invalid entry name: potential malicious use of relative path"
+        );
+
+        output_paths.push((i, output_path));
+    }
+
+    std::fs::create_dir_all(destination)
+        .with_context(|| "Failed to create destination directory")?;
+
+    for (i, output_path) in output_paths {
+        let mut zip_file = zip
+            .by_index(i)
+            .with_context(|| format!("failed to read entry at index {}", i))?;
+        let mtime = resolve_mtime(zip_file.last_modified(), reproducible, fallback_timestamp);
+
+        if let Some(parent_path) = output_path.parent() {
+            if !parent_path.exists() {
+                std::fs::create_dir_all(parent_path)?;
+            }
+        }
+
+        let mut output_file = std::fs::File::create(&output_path)?;
+        std::io::copy(&mut zip_file, &mut output_file)?;
+        filetime::set_file_mtime(&output_path, mtime)
+            .with_context(|| format!("failed to set mtime on {:?}", output_path))?;
+    }
+
+    Ok(())
 }
 
 /// Extracts the files from a single layer from the Jar.
-fn extract_layer(
-    zip: &mut ZipArchive<Cursor<&[u8]>>,
+fn extract_layer<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
     destination: &PathBuf,
     layer: &str,
     files: Vec<String>,
+    reproducible: bool,
+    fallback_timestamp: i64,
 ) -> anyhow::Result<()> {
     let file_names: Vec<String> = zip.file_names().into_iter().map(String::from).collect();
 
@@ -228,15 +479,20 @@ fn extract_layer(
                     let mut zip_file = zip.by_name(zip_entry).with_context(|| {
                         format!("unknown (child) file {} in layer {}", zip_entry, layer)
                     })?;
+                    let mtime =
+                        resolve_mtime(zip_file.last_modified(), reproducible, fallback_timestamp);
 
                     let mut output_file = std::fs::File::create(&output_path)?;
                     std::io::copy(&mut zip_file, &mut output_file)?;
+                    filetime::set_file_mtime(&output_path, mtime)
+                        .with_context(|| format!("failed to set mtime on {:?}", output_path))?;
                 }
             }
         } else {
             let mut zip_file = zip
                 .by_name(entry)
                 .with_context(|| format!("unknown file {} in layer {}", entry, layer))?;
+            let mtime = resolve_mtime(zip_file.last_modified(), reproducible, fallback_timestamp);
 
             let entry = zip_file
                 .enclosed_name()
@@ -252,6 +508,8 @@ fn extract_layer(
 
             let mut output_file = std::fs::File::create(&output_path)?;
             std::io::copy(&mut zip_file, &mut output_file)?;
+            filetime::set_file_mtime(&output_path, mtime)
+                .with_context(|| format!("failed to set mtime on {:?}", output_path))?;
         }
     }
 
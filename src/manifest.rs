@@ -3,17 +3,32 @@ use std::io::BufReader;
 use std::str::FromStr;
 
 use anyhow::Context;
-use itertools::FoldWhile::{Continue, Done};
-use itertools::Itertools;
 use zip::ZipArchive;
 
 const PROP_LAYERS_INDEX: &str = "Spring-Boot-Layers-Index";
 const PROP_CLASSPATH_INDEX: &str = "Spring-Boot-Classpath-Index";
+const PROP_CLASSES: &str = "Spring-Boot-Classes";
+const PROP_LIB: &str = "Spring-Boot-Lib";
+const PROP_MAIN_CLASS: &str = "Main-Class";
+const PROP_START_CLASS: &str = "Start-Class";
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct JarManifest {
     pub layers_index: String,
     pub classpath_index: String,
+    /// The root directory holding the application's own classes and resources (e.g.
+    /// `BOOT-INF/classes/`). Only present on jars built with a recent enough Spring Boot plugin;
+    /// required by `tools::extract_tools`, but not by `list`/`classpath`/plain `extract`.
+    pub classes_dir: Option<String>,
+    /// The root directory holding the application's third-party dependency jars (e.g.
+    /// `BOOT-INF/lib/`). Same availability caveat as `classes_dir`.
+    pub lib_dir: Option<String>,
+    /// The JVM entrypoint that Java itself launches (`java -jar`): for a layered Spring Boot jar
+    /// this is always the loader's own launcher class, not the application.
+    pub main_class: Option<String>,
+    /// The application's actual entrypoint, as resolved by the Spring Boot loader (or by
+    /// Paketo/native-image metadata). This is what `run` should launch.
+    pub start_class: Option<String>,
 }
 
 impl FromStr for JarManifest {
@@ -37,39 +52,29 @@ impl JarManifest {
         .with_context(|| "Failed to read Jar Manifest")
     }
 
-    fn from_lines<R: Iterator<Item = String>>(mut iter: R) -> anyhow::Result<Self> {
-        let (layers_index, classpath_index) = iter
-            .fold_while(
-                (None as Option<String>, None as Option<String>),
-                |(layers_index, classpath_index), line| {
-                    let result = if line.starts_with(PROP_LAYERS_INDEX) {
-                        (
-                            line.split_once(':')
-                                .map(|x| x.1)
-                                .map(str::trim_start)
-                                .map(String::from),
-                            classpath_index,
-                        )
-                    } else if line.starts_with(PROP_CLASSPATH_INDEX) {
-                        (
-                            layers_index,
-                            line.split_once(':')
-                                .map(|x| x.1)
-                                .map(str::trim_start)
-                                .map(String::from),
-                        )
-                    } else {
-                        (layers_index, classpath_index)
-                    };
-
-                    if result.0.is_none() || result.1.is_none() {
-                        Continue(result)
-                    } else {
-                        Done(result)
-                    }
-                },
-            )
-            .into_inner();
+    fn from_lines<R: Iterator<Item = String>>(iter: R) -> anyhow::Result<Self> {
+        let mut layers_index = None;
+        let mut classpath_index = None;
+        let mut classes_dir = None;
+        let mut lib_dir = None;
+        let mut main_class = None;
+        let mut start_class = None;
+
+        for line in iter {
+            if let Some(value) = property(&line, PROP_LAYERS_INDEX) {
+                layers_index = Some(value);
+            } else if let Some(value) = property(&line, PROP_CLASSPATH_INDEX) {
+                classpath_index = Some(value);
+            } else if let Some(value) = property(&line, PROP_CLASSES) {
+                classes_dir = Some(value);
+            } else if let Some(value) = property(&line, PROP_LIB) {
+                lib_dir = Some(value);
+            } else if let Some(value) = property(&line, PROP_MAIN_CLASS) {
+                main_class = Some(value);
+            } else if let Some(value) = property(&line, PROP_START_CLASS) {
+                start_class = Some(value);
+            }
+        }
 
         Ok(Self {
             layers_index: layers_index.with_context(|| {
@@ -81,10 +86,22 @@ impl JarManifest {
                     PROP_CLASSPATH_INDEX
                 )
             })?,
+            classes_dir,
+            lib_dir,
+            main_class,
+            start_class,
         })
     }
 }
 
+/// Parses a `Name: value` MANIFEST.MF line into `value`, if `line` declares `name`.
+fn property(line: &str, name: &str) -> Option<String> {
+    line.starts_with(name)
+        .then(|| line.split_once(':').map(|(_, value)| value.trim_start()))
+        .flatten()
+        .map(String::from)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -95,6 +112,8 @@ Spring-Boot-Classes: BOOT-INF/classes/
 Spring-Boot-Lib: BOOT-INF/lib/
 Spring-Boot-Classpath-Index: BOOT-INF/classpath.idx
 Spring-Boot-Layers-Index: BOOT-INF/layers.idx
+Start-Class: com.example.Application
+Main-Class: org.springframework.boot.loader.JarLauncher
 ";
 
     const INVALID_MANIFEST_NO_LAYERS: &str = "
@@ -123,6 +142,10 @@ Created-By: Maven JAR Plugin 3.2.2
             JarManifest {
                 layers_index: "BOOT-INF/layers.idx".into(),
                 classpath_index: "BOOT-INF/classpath.idx".into(),
+                classes_dir: Some("BOOT-INF/classes/".into()),
+                lib_dir: Some("BOOT-INF/lib/".into()),
+                main_class: Some("org.springframework.boot.loader.JarLauncher".into()),
+                start_class: Some("com.example.Application".into()),
             }
         );
 
@@ -135,4 +158,38 @@ Created-By: Maven JAR Plugin 3.2.2
         JarManifest::from_str(INVALID_MANIFEST_NO_CLASSPATH)
             .expect_err("Should not be able to parse Jar missing classpath index");
     }
+
+    const VALID_MANIFEST_NO_START_CLASS: &str = "
+Spring-Boot-Version: 2.7.1
+Spring-Boot-Classes: BOOT-INF/classes/
+Spring-Boot-Lib: BOOT-INF/lib/
+Spring-Boot-Classpath-Index: BOOT-INF/classpath.idx
+Spring-Boot-Layers-Index: BOOT-INF/layers.idx
+Main-Class: org.springframework.boot.loader.JarLauncher
+";
+
+    #[test]
+    fn parse_without_start_class() {
+        let manifest = JarManifest::from_str(VALID_MANIFEST_NO_START_CLASS).unwrap();
+        assert_eq!(
+            manifest.main_class,
+            Some("org.springframework.boot.loader.JarLauncher".into())
+        );
+        assert_eq!(manifest.start_class, None);
+    }
+
+    const VALID_MANIFEST_NO_TOOLS_METADATA: &str = "
+Spring-Boot-Version: 2.7.1
+Spring-Boot-Classpath-Index: BOOT-INF/classpath.idx
+Spring-Boot-Layers-Index: BOOT-INF/layers.idx
+Start-Class: com.example.Application
+Main-Class: org.springframework.boot.loader.JarLauncher
+";
+
+    #[test]
+    fn parse_without_classes_and_lib() {
+        let manifest = JarManifest::from_str(VALID_MANIFEST_NO_TOOLS_METADATA).unwrap();
+        assert_eq!(manifest.classes_dir, None);
+        assert_eq!(manifest.lib_dir, None);
+    }
 }
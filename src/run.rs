@@ -0,0 +1,231 @@
+//! The `run` subcommand resolves the application's classpath and start class from the Jar and
+//! launches it directly with `java`, without requiring the user to extract layers first.
+
+use std::hash::{Hash, Hasher};
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::Context;
+use yaml_rust::YamlLoader;
+use zip::ZipArchive;
+
+use crate::manifest::JarManifest;
+
+/// Resolves the classpath and start class for the Jar, extracting the application classes and
+/// dependency jars into a cache directory keyed by `cache_key`, then spawns `java` against them,
+/// forwarding `extra_args` and propagating the child's exit code.
+pub fn run<R: Read + Seek>(
+    mut zip: ZipArchive<R>,
+    manifest: JarManifest,
+    cache_key: &str,
+    extra_args: Vec<&str>,
+) -> anyhow::Result<()> {
+    let start_class = manifest
+        .start_class
+        .as_ref()
+        .or(manifest.main_class.as_ref())
+        .with_context(|| "MANIFEST.MF is missing both 'Start-Class' and 'Main-Class'")?;
+    let classes_src_dir = manifest
+        .classes_dir
+        .as_ref()
+        .with_context(|| "MANIFEST.MF is missing 'Spring-Boot-Classes'")?;
+
+    let cache_dir = cache_dir_for(cache_key)?;
+
+    let classes_dir = cache_dir.join("classes");
+    extract_tree(&mut zip, &classes_dir, classes_src_dir)
+        .with_context(|| "Failed to extract application classes")?;
+
+    let lib_dir = cache_dir.join("lib");
+    std::fs::create_dir_all(&lib_dir)
+        .with_context(|| format!("failed to create lib directory: {:?}", lib_dir))?;
+
+    let mut classpath = vec![classes_dir];
+    for entry in classpath_entries(&mut zip, &manifest)? {
+        classpath.push(extract_classpath_jar(&mut zip, &lib_dir, &entry)?);
+    }
+
+    let status = Command::new(java_binary())
+        .arg("-cp")
+        .arg(std::env::join_paths(&classpath).with_context(|| "failed to build classpath")?)
+        .arg(start_class)
+        .args(extra_args)
+        .status()
+        .with_context(|| "failed to launch java")?;
+
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+/// Reads the ordered list of dependency jar paths (relative to the Jar root) out of
+/// `classpath.idx`.
+fn classpath_entries<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    manifest: &JarManifest,
+) -> anyhow::Result<Vec<String>> {
+    let index = {
+        let mut index_entry = zip
+            .by_name(&manifest.classpath_index)
+            .with_context(|| "Failed to open classpath index")?;
+        let mut contents = String::new();
+        index_entry
+            .read_to_string(&mut contents)
+            .with_context(|| "Failed to read classpath index")?;
+        contents
+    };
+
+    let entries = YamlLoader::load_from_str(&index)
+        .with_context(|| "Failed to parse classpath index")?
+        .into_iter()
+        .next()
+        .with_context(|| "Invalid classpath index yaml: expected 1 root")?
+        .as_vec()
+        .with_context(|| "Invalid classpath index yaml: expected array")?
+        .iter()
+        .flat_map(|entry| entry.as_str())
+        .map(String::from)
+        .collect();
+
+    Ok(entries)
+}
+
+/// Extracts a single dependency jar entry into `lib_dir`, skipping the copy if it was already
+/// extracted by a previous `run`, and returns its absolute path.
+fn extract_classpath_jar<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    lib_dir: &Path,
+    entry: &str,
+) -> anyhow::Result<PathBuf> {
+    let file_name = Path::new(entry)
+        .file_name()
+        .with_context(|| format!("invalid classpath entry: {}", entry))?;
+    let output_path = lib_dir.join(file_name);
+
+    if !output_path.exists() {
+        let mut zip_file = zip
+            .by_name(entry)
+            .with_context(|| format!("unknown classpath entry: {}", entry))?;
+        let mut output_file = std::fs::File::create(&output_path)
+            .with_context(|| format!("failed to create {:?}", output_path))?;
+        std::io::copy(&mut zip_file, &mut output_file)?;
+    }
+
+    Ok(output_path)
+}
+
+/// Extracts every entry under `prefix` into `destination`, stripped of the prefix. Does nothing
+/// if `destination` already exists, so repeated `run`s of the same jar reuse the cache.
+fn extract_tree<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    destination: &Path,
+    prefix: &str,
+) -> anyhow::Result<()> {
+    if destination.exists() {
+        return Ok(());
+    }
+
+    let file_names: Vec<String> = zip.file_names().map(String::from).collect();
+    for name in file_names {
+        if !name.starts_with(prefix) || name.ends_with('/') {
+            continue;
+        }
+
+        let relative = Path::new(&name)
+            .strip_prefix(prefix)
+            .with_context(|| format!("failed to relativize {} to {}", name, prefix))?;
+        let output_path = destination.join(relative);
+        anyhow::ensure!(
+            output_path.starts_with(destination),
+            "invalid entry name: potential malicious use of relative path"
+        );
+
+        if let Some(parent_path) = output_path.parent() {
+            std::fs::create_dir_all(parent_path)?;
+        }
+
+        let mut zip_file = zip
+            .by_name(&name)
+            .with_context(|| format!("unknown file: {}", name))?;
+        let mut output_file = std::fs::File::create(&output_path)?;
+        std::io::copy(&mut zip_file, &mut output_file)?;
+    }
+
+    Ok(())
+}
+
+/// Computes a cache directory under the system temp directory, keyed by `cache_key` and the
+/// current user, so repeated `run`s of the same jar (or, for a jar streamed from stdin, the same
+/// content) reuse the extracted classpath instead of re-extracting it every time.
+///
+/// `/tmp` is usually world-writable, so the per-jar directory is created owner-only (`0o700`) and
+/// its name salted with the current user: without this, another local user could predict the
+/// cache path ahead of time and seed it with malicious classes/jars for `run` to silently execute.
+fn cache_dir_for(cache_key: &str) -> anyhow::Result<PathBuf> {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    current_user().hash(&mut hasher);
+    cache_key.hash(&mut hasher);
+
+    let root_dir = std::env::temp_dir().join("spring-boot-layertools");
+    std::fs::create_dir_all(&root_dir)
+        .with_context(|| format!("failed to create cache root: {:?}", root_dir))?;
+
+    let cache_dir = root_dir.join(format!("{:x}", hasher.finish()));
+    create_or_verify_private_dir(&cache_dir)?;
+
+    Ok(cache_dir)
+}
+
+/// An identifier for the current user, used to salt the cache directory name so it isn't
+/// predictable from the jar's path/size/mtime (or content) alone.
+fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .unwrap_or_default()
+}
+
+/// Creates `dir` as an owner-only (`0o700`) directory, or, if it already exists, verifies it's
+/// still owner-only before trusting it as a cache hit. Refuses to reuse a pre-existing directory
+/// with looser permissions, since on a multi-user box that could be another user's attempt to
+/// seed the cache with malicious content ahead of a victim's first `run`.
+#[cfg(unix)]
+fn create_or_verify_private_dir(dir: &Path) -> anyhow::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    match std::fs::DirBuilder::new().mode(0o700).create(dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            let mode = std::fs::symlink_metadata(dir)
+                .with_context(|| format!("failed to stat cache directory: {:?}", dir))?
+                .permissions()
+                .mode()
+                & 0o777;
+            anyhow::ensure!(
+                mode == 0o700,
+                "cache directory {:?} already exists with unexpected permissions ({:o}); refusing \
+                 to reuse it, as this may be a cache-poisoning attempt by another user",
+                dir,
+                mode
+            );
+            Ok(())
+        }
+        Err(e) => Err(e).with_context(|| format!("failed to create cache directory: {:?}", dir)),
+    }
+}
+
+#[cfg(not(unix))]
+fn create_or_verify_private_dir(dir: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create cache directory: {:?}", dir))
+}
+
+/// Locates the `java` binary, preferring `$JAVA_HOME/bin/java` when set and present.
+fn java_binary() -> PathBuf {
+    if let Ok(java_home) = std::env::var("JAVA_HOME") {
+        let candidate = PathBuf::from(java_home).join("bin").join("java");
+        if candidate.exists() {
+            return candidate;
+        }
+    }
+
+    PathBuf::from("java")
+}
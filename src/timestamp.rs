@@ -0,0 +1,129 @@
+//! Resolves the modification time to stamp extracted files with, either preserving each Jar
+//! entry's own DOS-encoded timestamp or, in reproducible mode, a single fixed timestamp shared
+//! by every extracted file.
+
+use filetime::FileTime;
+use zip::DateTime;
+
+/// 1980-01-01T00:00:00Z: the epoch of the DOS timestamp format used by zip entries, and the
+/// fallback used when an entry doesn't carry a valid timestamp and no `--timestamp` was given.
+pub const FALLBACK_TIMESTAMP: i64 = 315532800;
+
+/// Resolves the mtime to apply to an extracted file.
+///
+/// In reproducible mode every file is stamped with `fallback` (the fixed epoch, typically the
+/// user-supplied `--timestamp` or [`FALLBACK_TIMESTAMP`]) regardless of its own timestamp.
+/// Otherwise, the entry's own `last_modified()` is used, falling back to `fallback` if it isn't a
+/// valid DOS timestamp.
+pub fn resolve_mtime(last_modified: DateTime, reproducible: bool, fallback: i64) -> FileTime {
+    if reproducible {
+        return FileTime::from_unix_time(fallback, 0);
+    }
+
+    let seconds = to_unix_timestamp(last_modified).unwrap_or(fallback);
+    FileTime::from_unix_time(seconds, 0)
+}
+
+/// Converts a resolved mtime back into a zip-writable `DateTime`, clamping to the DOS-encodable
+/// range (1980-2107) so entries stamped with an out-of-range `--timestamp` still produce a valid
+/// zip entry rather than a write error.
+pub fn to_zip_datetime(mtime: FileTime) -> DateTime {
+    let (year, month, day, hour, minute, second) = civil_from_unix_time(mtime.seconds());
+    let year = year.clamp(1980, 2107) as u16;
+    DateTime::from_date_and_time(year, month, day, hour, minute, second)
+        .unwrap_or_else(|_| DateTime::default())
+}
+
+/// Converts Unix seconds into a proleptic Gregorian civil date and time-of-day.
+fn civil_from_unix_time(unix_time: i64) -> (i64, u8, u8, u8, u8, u8) {
+    let days = unix_time.div_euclid(86400);
+    let seconds_of_day = unix_time.rem_euclid(86400);
+
+    let (year, month, day) = civil_from_days(days);
+    let hour = (seconds_of_day / 3600) as u8;
+    let minute = ((seconds_of_day % 3600) / 60) as u8;
+    let second = (seconds_of_day % 60) as u8;
+
+    (year, month as u8, day as u8, hour, minute, second)
+}
+
+/// Days since the Unix epoch back to a proleptic Gregorian `(year, month, day)`. Inverse of
+/// [`days_from_civil`], same Howard Hinnant source.
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = if m <= 2 { y + 1 } else { y };
+    (y, m, d)
+}
+
+/// Converts a zip entry's DOS-encoded `last_modified()` into Unix seconds, or `None` if the
+/// entry's date is outside the representable DOS range (1980-2107).
+fn to_unix_timestamp(dt: DateTime) -> Option<i64> {
+    let year = dt.year() as i64;
+    let month = dt.month() as i64;
+    let day = dt.day() as i64;
+
+    if !(1980..=2107).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    let seconds_of_day = dt.hour() as i64 * 3600 + dt.minute() as i64 * 60 + dt.second() as i64;
+    Some(days * 86400 + seconds_of_day)
+}
+
+/// Days since the Unix epoch (1970-01-01) for a proleptic Gregorian date. Port of Howard
+/// Hinnant's `days_from_civil` (<http://howardhinnant.github.io/date_algorithms.html>).
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_entry_timestamp() {
+        let dt = DateTime::from_date_and_time(2022, 7, 15, 10, 30, 0).unwrap();
+        let mtime = resolve_mtime(dt, false, FALLBACK_TIMESTAMP);
+        assert_eq!(mtime, FileTime::from_unix_time(1657881000, 0));
+    }
+
+    #[test]
+    fn falls_back_to_fixed_timestamp_when_reproducible() {
+        let dt = DateTime::from_date_and_time(2022, 7, 15, 10, 30, 0).unwrap();
+        let mtime = resolve_mtime(dt, true, FALLBACK_TIMESTAMP);
+        assert_eq!(mtime, FileTime::from_unix_time(FALLBACK_TIMESTAMP, 0));
+    }
+
+    #[test]
+    fn falls_back_to_fixed_timestamp_when_entry_date_invalid() {
+        // Month 0 and day 0: not a valid DOS date.
+        let mtime = resolve_mtime(DateTime::from_msdos(0, 0), false, FALLBACK_TIMESTAMP);
+        assert_eq!(mtime, FileTime::from_unix_time(FALLBACK_TIMESTAMP, 0));
+    }
+
+    #[test]
+    fn round_trips_mtime_through_zip_datetime() {
+        let mtime = FileTime::from_unix_time(1657881000, 0);
+        let dt = to_zip_datetime(mtime);
+        assert_eq!(
+            (dt.year(), dt.month(), dt.day(), dt.hour(), dt.minute(), dt.second()),
+            (2022, 7, 15, 10, 30, 0)
+        );
+        assert_eq!(resolve_mtime(dt, false, FALLBACK_TIMESTAMP), mtime);
+    }
+}
@@ -0,0 +1,208 @@
+//! A `tools`-style extraction mode, mirroring Spring Boot's own `jarmode tools`: splits the Jar
+//! into a slim, directly runnable `application.jar` holding just the application's own classes,
+//! and a sibling `lib/` directory holding the third-party dependency jars it references via the
+//! jar manifest's `Class-Path` attribute. This is the split container image layering tools expect
+//! instead of the layer-name directory wrappers produced by the default `extract` mode.
+
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+use anyhow::Context;
+use filetime::FileTime;
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use crate::manifest::JarManifest;
+use crate::timestamp::{resolve_mtime, to_zip_datetime};
+
+/// The name of the slim application jar written to the destination directory.
+const APPLICATION_JAR: &str = "application.jar";
+
+/// Splits the Jar into `application.jar` and a sibling `lib/` directory.
+pub fn extract_tools<R: Read + Seek>(
+    mut zip: ZipArchive<R>,
+    manifest: JarManifest,
+    destination: &Path,
+    reproducible: bool,
+    fallback_timestamp: i64,
+    force: bool,
+) -> anyhow::Result<()> {
+    let classes_dir = manifest
+        .classes_dir
+        .as_ref()
+        .with_context(|| "MANIFEST.MF is missing 'Spring-Boot-Classes'; tools mode requires it")?;
+    let lib_src_dir = manifest
+        .lib_dir
+        .as_ref()
+        .with_context(|| "MANIFEST.MF is missing 'Spring-Boot-Lib'; tools mode requires it")?;
+
+    let lib_dir = destination.join("lib");
+    let application_jar = destination.join(APPLICATION_JAR);
+    crate::ensure_extraction_allowed(&lib_dir, force)?;
+    crate::ensure_extraction_allowed(&application_jar, force)?;
+
+    std::fs::create_dir_all(destination)
+        .with_context(|| "Failed to create destination directory")?;
+    std::fs::create_dir_all(&lib_dir)
+        .with_context(|| format!("failed to create lib directory: {:?}", lib_dir))?;
+
+    let lib_names = extract_libraries(
+        &mut zip,
+        lib_src_dir,
+        &lib_dir,
+        reproducible,
+        fallback_timestamp,
+    )?;
+
+    write_application_jar(
+        &mut zip,
+        &manifest,
+        classes_dir,
+        &application_jar,
+        &lib_names,
+        reproducible,
+        fallback_timestamp,
+    )
+}
+
+/// Extracts every jar under `lib_src_dir` (the manifest's `Spring-Boot-Lib` root) into `lib_dir`,
+/// returning the file names written, for the application jar's `Class-Path`.
+fn extract_libraries<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    lib_src_dir: &str,
+    lib_dir: &Path,
+    reproducible: bool,
+    fallback_timestamp: i64,
+) -> anyhow::Result<Vec<String>> {
+    let file_names: Vec<String> = zip.file_names().map(String::from).collect();
+    let mut lib_names = Vec::new();
+
+    for name in file_names {
+        if !name.starts_with(lib_src_dir) || name.ends_with('/') {
+            continue;
+        }
+
+        let file_name = Path::new(&name)
+            .file_name()
+            .with_context(|| format!("invalid library entry: {}", name))?
+            .to_owned();
+        let output_path = lib_dir.join(&file_name);
+
+        let mut zip_file = zip
+            .by_name(&name)
+            .with_context(|| format!("unknown library entry: {}", name))?;
+        let mtime = resolve_mtime(zip_file.last_modified(), reproducible, fallback_timestamp);
+
+        let mut output_file = std::fs::File::create(&output_path)
+            .with_context(|| format!("failed to create {:?}", output_path))?;
+        std::io::copy(&mut zip_file, &mut output_file)?;
+        filetime::set_file_mtime(&output_path, mtime)
+            .with_context(|| format!("failed to set mtime on {:?}", output_path))?;
+
+        lib_names.push(file_name.to_string_lossy().into_owned());
+    }
+
+    lib_names.sort();
+    Ok(lib_names)
+}
+
+/// Writes the slim application jar: every entry under the manifest's `Spring-Boot-Classes` root,
+/// plus a manifest declaring the original start/main class and a `Class-Path` pointing at
+/// `lib_names`.
+fn write_application_jar<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    manifest: &JarManifest,
+    classes_dir: &str,
+    output_path: &Path,
+    lib_names: &[String],
+    reproducible: bool,
+    fallback_timestamp: i64,
+) -> anyhow::Result<()> {
+    let output_file = std::fs::File::create(output_path)
+        .with_context(|| format!("failed to create {:?}", output_path))?;
+    let mut writer = ZipWriter::new(output_file);
+
+    let fixed_mtime = FileTime::from_unix_time(fallback_timestamp, 0);
+    let manifest_options = if reproducible {
+        FileOptions::default().last_modified_time(to_zip_datetime(fixed_mtime))
+    } else {
+        FileOptions::default()
+    };
+    writer.start_file("META-INF/MANIFEST.MF", manifest_options)?;
+    write!(writer, "Manifest-Version: 1.0\r\n")?;
+    if let Some(main_class) = manifest
+        .start_class
+        .as_ref()
+        .or(manifest.main_class.as_ref())
+    {
+        write_manifest_attribute(&mut writer, "Main-Class", main_class)?;
+    }
+    if !lib_names.is_empty() {
+        let class_path = lib_names
+            .iter()
+            .map(|name| format!("lib/{}", name))
+            .collect::<Vec<_>>()
+            .join(" ");
+        write_manifest_attribute(&mut writer, "Class-Path", &class_path)?;
+    }
+
+    let file_names: Vec<String> = zip.file_names().map(String::from).collect();
+    for name in file_names {
+        if !name.starts_with(classes_dir) || name.ends_with('/') {
+            continue;
+        }
+
+        let relative = name
+            .strip_prefix(classes_dir)
+            .with_context(|| format!("failed to relativize {} to {}", name, classes_dir))?;
+
+        let mut zip_file = zip
+            .by_name(&name)
+            .with_context(|| format!("unknown class entry: {}", name))?;
+        let mtime = resolve_mtime(zip_file.last_modified(), reproducible, fallback_timestamp);
+        let options = FileOptions::default().last_modified_time(to_zip_datetime(mtime));
+
+        writer.start_file(relative, options)?;
+        std::io::copy(&mut zip_file, &mut writer)?;
+    }
+
+    writer
+        .finish()
+        .with_context(|| "failed to finalize application jar")?;
+    if reproducible {
+        filetime::set_file_mtime(output_path, fixed_mtime)
+            .with_context(|| format!("failed to set mtime on {:?}", output_path))?;
+    }
+    Ok(())
+}
+
+/// Writes a single manifest attribute (`name: value`), wrapping continuation lines per the Jar
+/// manifest spec: each physical line, including its CRLF terminator, is capped at 72 bytes, and
+/// continuation lines are introduced by a single leading space. Without this, a `Class-Path` with
+/// more than a couple of entries overflows one line and corrupts the manifest.
+fn write_manifest_attribute<W: Write>(
+    writer: &mut W,
+    name: &str,
+    value: &str,
+) -> anyhow::Result<()> {
+    let line = format!("{}: {}", name, value);
+    let bytes = line.as_bytes();
+
+    let mut start = 0;
+    let mut first = true;
+    while start < bytes.len() || first {
+        let max = if first { 70 } else { 69 };
+        let end = (start + max).min(bytes.len());
+
+        if !first {
+            writer.write_all(b" ")?;
+        }
+        writer.write_all(&bytes[start..end])?;
+        write!(writer, "\r\n")?;
+
+        start = end;
+        first = false;
+    }
+
+    Ok(())
+}